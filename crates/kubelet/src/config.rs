@@ -13,6 +13,7 @@
 
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[cfg(any(feature = "cli", feature = "docs"))]
 use std::iter::FromIterator;
@@ -60,6 +61,24 @@ pub struct Config {
     /// Registries that should be accessed using HTTP instead of
     /// HTTPS.
     pub insecure_registries: Option<Vec<String>>,
+    /// Whether to accept registry TLS certificates that cannot be verified,
+    /// such as self-signed certificates.
+    ///
+    /// This is dangerous: it disables certificate validation for every
+    /// registry, so a man-in-the-middle can serve arbitrary modules. Only
+    /// enable it for development or fully trusted networks.
+    pub accept_invalid_registry_certificates: bool,
+    /// How long a cached image's digest is trusted before it is checked
+    /// against the registry again. If `None`, images pulled with the Always
+    /// policy are revalidated on every pull, and images pulled with
+    /// IfNotPresent are never revalidated.
+    pub image_revalidation_interval: Option<Duration>,
+    /// If set, the only registries that images may be fetched from. Images
+    /// from any other registry are rejected without contacting it.
+    pub allowed_registries: Option<Vec<String>>,
+    /// Registries that images must never be fetched from, even if they are
+    /// also allowed or already cached.
+    pub denied_registries: Option<Vec<String>>,
     /// The directory kubelet should watch for new plugin sockets
     pub plugins_dir: PathBuf,
     /// The directory where kubelet's Registration service for
@@ -123,6 +142,14 @@ struct ConfigBuilder {
     pub allow_local_modules: Option<bool>,
     #[serde(default, rename = "insecureRegistries")]
     pub insecure_registries: Option<Vec<String>>,
+    #[serde(default, rename = "acceptInvalidRegistryCertificates")]
+    pub accept_invalid_registry_certificates: Option<bool>,
+    #[serde(default, rename = "imageRevalidationIntervalSeconds")]
+    pub image_revalidation_interval_seconds: Option<u64>,
+    #[serde(default, rename = "allowedRegistries")]
+    pub allowed_registries: Option<Vec<String>>,
+    #[serde(default, rename = "deniedRegistries")]
+    pub denied_registries: Option<Vec<String>>,
     #[serde(default, rename = "pluginsDir")]
     pub plugins_dir: Option<PathBuf>,
     #[serde(default, rename = "devicePluginsDir")]
@@ -163,6 +190,10 @@ impl Config {
             bootstrap_file: PathBuf::from(BOOTSTRAP_FILE),
             allow_local_modules: false,
             insecure_registries: None,
+            accept_invalid_registry_certificates: false,
+            image_revalidation_interval: None,
+            allowed_registries: None,
+            denied_registries: None,
             plugins_dir,
             device_plugins_dir,
             server_config: ServerConfig {
@@ -287,6 +318,10 @@ impl ConfigBuilder {
             max_pods: ok_result_of(opts.max_pods),
             allow_local_modules: opts.allow_local_modules,
             insecure_registries: opts.insecure_registries.map(parse_comma_separated),
+            accept_invalid_registry_certificates: opts.accept_invalid_registry_certificates,
+            image_revalidation_interval_seconds: opts.image_revalidation_interval_seconds,
+            allowed_registries: opts.allowed_registries.map(parse_comma_separated),
+            denied_registries: opts.denied_registries.map(parse_comma_separated),
             plugins_dir: opts.plugins_dir,
             device_plugins_dir: opts.device_plugins_dir,
             server_addr: ok_result_of(opts.addr),
@@ -326,6 +361,14 @@ impl ConfigBuilder {
             bootstrap_file: other.bootstrap_file.or(self.bootstrap_file),
             allow_local_modules: other.allow_local_modules.or(self.allow_local_modules),
             insecure_registries: other.insecure_registries.or(self.insecure_registries),
+            accept_invalid_registry_certificates: other
+                .accept_invalid_registry_certificates
+                .or(self.accept_invalid_registry_certificates),
+            image_revalidation_interval_seconds: other
+                .image_revalidation_interval_seconds
+                .or(self.image_revalidation_interval_seconds),
+            allowed_registries: other.allowed_registries.or(self.allowed_registries),
+            denied_registries: other.denied_registries.or(self.denied_registries),
             plugins_dir: other.plugins_dir.or(self.plugins_dir),
             device_plugins_dir: other.device_plugins_dir.or(self.device_plugins_dir),
             server_tls_private_key_file: other
@@ -382,6 +425,14 @@ impl ConfigBuilder {
             bootstrap_file,
             allow_local_modules: self.allow_local_modules.unwrap_or(false),
             insecure_registries: self.insecure_registries,
+            accept_invalid_registry_certificates: self
+                .accept_invalid_registry_certificates
+                .unwrap_or(false),
+            image_revalidation_interval: self
+                .image_revalidation_interval_seconds
+                .map(Duration::from_secs),
+            allowed_registries: self.allowed_registries,
+            denied_registries: self.denied_registries,
             plugins_dir,
             device_plugins_dir,
             server_config: ServerConfig {
@@ -541,6 +592,34 @@ pub struct Opts {
         help = "Registries that should be accessed over HTTP instead of HTTPS (comma separated)"
     )]
     insecure_registries: Option<String>,
+
+    #[structopt(
+        long = "accept-invalid-registry-certificates",
+        env = "KRUSTLET_ACCEPT_INVALID_REGISTRY_CERTIFICATES",
+        help = "(Dangerous) Whether to accept registry TLS certificates that cannot be verified, such as self-signed certificates"
+    )]
+    accept_invalid_registry_certificates: Option<bool>,
+
+    #[structopt(
+        long = "image-revalidation-interval-seconds",
+        env = "KRUSTLET_IMAGE_REVALIDATION_INTERVAL_SECONDS",
+        help = "How long a cached image's digest is trusted before it is checked against the registry again. Defaults to revalidating on every Always pull and never for IfNotPresent"
+    )]
+    image_revalidation_interval_seconds: Option<u64>,
+
+    #[structopt(
+        long = "allowed-registries",
+        env = "KRUSTLET_ALLOWED_REGISTRIES",
+        help = "If set, the only registries images may be fetched from (comma separated)"
+    )]
+    allowed_registries: Option<String>,
+
+    #[structopt(
+        long = "denied-registries",
+        env = "KRUSTLET_DENIED_REGISTRIES",
+        help = "Registries images must never be fetched from (comma separated)"
+    )]
+    denied_registries: Option<String>,
 }
 
 fn default_hostname() -> anyhow::Result<String> {
@@ -687,6 +766,10 @@ mod test {
                 "local",
                 "dev"
             ],
+            "acceptInvalidRegistryCertificates": true,
+            "imageRevalidationIntervalSeconds": 300,
+            "allowedRegistries": ["webassembly.azurecr.io"],
+            "deniedRegistries": ["docker.io"],
             "pluginsDir": "/some/plugins"
         }"#,
         );
@@ -716,6 +799,16 @@ mod test {
         assert_eq!(config.insecure_registries.clone().unwrap().len(), 2);
         assert_eq!(&config.insecure_registries.clone().unwrap()[0], "local");
         assert_eq!(&config.insecure_registries.unwrap()[1], "dev");
+        assert!(config.accept_invalid_registry_certificates);
+        assert_eq!(
+            config.image_revalidation_interval,
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            config.allowed_registries,
+            Some(vec!["webassembly.azurecr.io".to_owned()])
+        );
+        assert_eq!(config.denied_registries, Some(vec!["docker.io".to_owned()]));
         assert_eq!(&config.plugins_dir.to_string_lossy(), "/some/plugins");
     }
 
@@ -777,6 +870,10 @@ mod test {
         assert_eq!(format!("{}", config.node_ip), "4.4.4.4");
         assert!(!config.allow_local_modules);
         assert_eq!(config.insecure_registries, None);
+        assert!(!config.accept_invalid_registry_certificates);
+        assert_eq!(config.image_revalidation_interval, None);
+        assert_eq!(config.allowed_registries, None);
+        assert_eq!(config.denied_registries, None);
         assert_eq!(config.node_labels.len(), 0);
         assert_eq!(
             &config.plugins_dir.to_string_lossy(),
@@ -813,6 +910,7 @@ mod test {
             "nodeName": "krusty-node",
             "allowLocalModules": true,
             "insecureRegistries": ["local1", "local2"],
+            "acceptInvalidRegistryCertificates": true,
             "pluginsDir": "/some/plugins",
             "tlsCertificateFile": "/my/secure/cert.pfx",
             "tlsPrivateKeyFile": "/the/key"
@@ -833,6 +931,7 @@ mod test {
             "nodeName": "krusty-node-2",
             "allowLocalModules": false,
             "insecureRegistries": ["local"],
+            "acceptInvalidRegistryCertificates": false,
             "pluginsDir": "/other/plugins",
             "tlsCertificateFile": "/my/secure/cert-2.pfx",
             "tlsPrivateKeyFile": "/the/2nd/key"
@@ -856,6 +955,7 @@ mod test {
         assert_eq!(config.data_dir.to_string_lossy(), "/krusty/data/dir/2");
         assert_eq!(format!("{}", config.node_ip), "173.183.193.22");
         assert!(!config.allow_local_modules);
+        assert!(!config.accept_invalid_registry_certificates);
         assert_eq!(config.insecure_registries.clone().unwrap().len(), 1);
         assert_eq!(&config.insecure_registries.clone().unwrap()[0], "local");
         assert_eq!(config.node_labels.len(), 2);
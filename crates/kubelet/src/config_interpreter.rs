@@ -12,6 +12,7 @@ impl ClientConfigSource for Config {
         };
         ClientConfig {
             protocol,
+            accept_invalid_certificates: self.accept_invalid_registry_certificates,
             ..Default::default()
         }
     }
@@ -32,6 +33,10 @@ mod test {
             data_dir: std::path::PathBuf::from("/nope"),
             hostname: "nope".to_owned(),
             insecure_registries: None,
+            accept_invalid_registry_certificates: false,
+            image_revalidation_interval: None,
+            allowed_registries: None,
+            denied_registries: None,
             plugins_dir: std::path::PathBuf::from("/nope"),
             device_plugins_dir: std::path::PathBuf::from("/nope"),
             max_pods: 0,
@@ -67,4 +72,23 @@ mod test {
             ClientProtocol::HttpsExcept(vec!["local".to_owned(), "dev".to_owned()]);
         assert_eq!(expected_protocol, client_config.protocol);
     }
+
+    #[test]
+    fn oci_config_rejects_invalid_certificates_by_default() {
+        let config = empty_config();
+        let client_config = config.client_config();
+        assert!(!client_config.accept_invalid_certificates);
+    }
+
+    #[test]
+    fn oci_config_respects_config_accept_invalid_registry_certificates() {
+        let config = Config {
+            accept_invalid_registry_certificates: true,
+            ..empty_config()
+        };
+
+        let client_config = config.client_config();
+
+        assert!(client_config.accept_invalid_certificates);
+    }
 }
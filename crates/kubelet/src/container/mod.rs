@@ -34,8 +34,10 @@ impl PullPolicy {
         match PullPolicy::parse(policy)? {
             Some(policy) => Ok(policy),
             None => match image {
-                Some(image) => match image.tag() {
-                    Some("latest") | None => Ok(PullPolicy::Always),
+                // A digest pins the image contents, so there is nothing to
+                // refresh even if the tag is mutable
+                Some(image) => match (image.tag(), image.digest()) {
+                    (Some("latest") | None, None) => Ok(PullPolicy::Always),
                     _ => Ok(PullPolicy::IfNotPresent),
                 },
                 None => Ok(PullPolicy::IfNotPresent),
@@ -119,6 +119,7 @@ pub mod node;
 pub mod plugin_watcher;
 pub mod pod;
 pub mod provider;
+pub mod registry;
 pub mod resources;
 pub mod secret;
 pub mod state;
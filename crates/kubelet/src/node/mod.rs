@@ -765,6 +765,10 @@ mod test {
             bootstrap_file: "doesnt/matter".into(),
             allow_local_modules: false,
             insecure_registries: None,
+            accept_invalid_registry_certificates: false,
+            image_revalidation_interval: None,
+            allowed_registries: None,
+            denied_registries: None,
             data_dir: PathBuf::new(),
             plugins_dir: PathBuf::new(),
             device_plugins_dir: PathBuf::new(),
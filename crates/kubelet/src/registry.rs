@@ -0,0 +1,40 @@
+//! Identifies the registries that image references point at
+
+/// The host names under which Docker Hub may appear in image references and
+/// in the registry keys of image pull secrets
+const DOCKER_HUB_REGISTRIES: &[&str] = &["docker.io", "index.docker.io", "registry-1.docker.io"];
+
+/// Whether the given registry (as returned by `Reference::registry`) is Docker Hub
+pub fn registry_is_docker_hub(registry: &str) -> bool {
+    DOCKER_HUB_REGISTRIES
+        .iter()
+        .any(|r| r.eq_ignore_ascii_case(registry))
+}
+
+/// Whether two registry host names refer to the same registry. Host names are
+/// case-insensitive, and all Docker Hub aliases are treated as one registry.
+pub(crate) fn same_registry(first: &str, second: &str) -> bool {
+    first.eq_ignore_ascii_case(second)
+        || (registry_is_docker_hub(first) && registry_is_docker_hub(second))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recognizes_docker_hub_aliases() {
+        assert!(registry_is_docker_hub("docker.io"));
+        assert!(registry_is_docker_hub("index.docker.io"));
+        assert!(registry_is_docker_hub("registry-1.docker.io"));
+        assert!(registry_is_docker_hub("Docker.io"));
+        assert!(!registry_is_docker_hub("myregistry.io"));
+    }
+
+    #[test]
+    fn matches_registries_case_insensitively() {
+        assert!(same_registry("MyRegistry.io", "myregistry.io"));
+        assert!(same_registry("docker.io", "Index.Docker.io"));
+        assert!(!same_registry("myregistry.io", "docker.io"));
+    }
+}
@@ -4,6 +4,8 @@ use k8s_openapi::api::core::v1::Secret;
 use kube::api::Api;
 use oci_distribution::secrets::RegistryAuth;
 
+use crate::registry::same_registry;
+
 /// Resolves registry authentication from image pull secrets
 pub struct RegistryAuthResolver {
     kube_client: kube::Client,
@@ -93,20 +95,167 @@ fn parse_auth_from_json_value(
     json_value: &serde_json::Value,
     registry_name: &str,
 ) -> Option<RegistryAuth> {
-    json_value
-        .get("auths")
-        .and_then(|auths| auths.get(registry_name))
-        .and_then(|creds| parse_auth_from_json_creds(creds))
+    let auths = json_value.get("auths")?;
+    // Prefer an exact match, then fall back to keys that name the same
+    // registry in another form, such as "https://index.docker.io/v1/"
+    let creds = auths.get(registry_name).or_else(|| {
+        auths.as_object().and_then(|auths| {
+            auths
+                .iter()
+                .find(|(key, _)| registry_key_matches(key, registry_name))
+                .map(|(_, creds)| creds)
+        })
+    })?;
+    parse_auth_from_json_creds(creds)
+}
+
+fn registry_key_matches(key: &str, registry_name: &str) -> bool {
+    let key = key
+        .trim_start_matches("https://")
+        .trim_start_matches("http://");
+    let host = key.split('/').next().unwrap_or(key);
+    same_registry(host, registry_name)
 }
 
 fn parse_auth_from_json_creds(json_creds: &serde_json::Value) -> Option<RegistryAuth> {
     let username = json_creds.get("username");
     let password = json_creds.get("password");
-    // TODO: my test creds also included an entry "auth" - should we return this? (e.g. bearer auth?)
     match (username, password) {
         (Some(serde_json::Value::String(u)), Some(serde_json::Value::String(p))) => {
             Some(RegistryAuth::Basic(u.to_owned(), p.to_owned()))
         }
-        _ => None,
+        _ => json_creds
+            .get("auth")
+            .and_then(|auth| auth.as_str())
+            .and_then(parse_auth_from_encoded_creds),
+    }
+}
+
+fn parse_auth_from_encoded_creds(encoded: &str) -> Option<RegistryAuth> {
+    // The "auth" entry is the base64 encoding of "username:password", which is
+    // what `docker login` writes when no separate username/password are stored
+    let decoded = base64::decode(encoded).ok()?;
+    let creds = String::from_utf8(decoded).ok()?;
+    let (username, password) = creds.split_once(':')?;
+    Some(RegistryAuth::Basic(
+        username.to_owned(),
+        password.to_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn secret_data(
+        json: serde_json::Value,
+    ) -> std::collections::BTreeMap<String, k8s_openapi::ByteString> {
+        let mut data = std::collections::BTreeMap::new();
+        data.insert(
+            ".dockerconfigjson".to_owned(),
+            k8s_openapi::ByteString(serde_json::to_vec(&json).unwrap()),
+        );
+        data
+    }
+
+    fn assert_basic(auth: Option<RegistryAuth>, username: &str, password: &str) {
+        match auth {
+            Some(RegistryAuth::Basic(u, p)) => {
+                assert_eq!(username, u);
+                assert_eq!(password, p);
+            }
+            _ => panic!("expected basic auth credentials"),
+        }
+    }
+
+    #[test]
+    fn can_parse_username_and_password() {
+        let data = secret_data(serde_json::json!({
+            "auths": {
+                "myregistry.io": { "username": "user", "password": "pass" }
+            }
+        }));
+        assert_basic(
+            parse_auth_from_secret_data(&data, "myregistry.io"),
+            "user",
+            "pass",
+        );
+    }
+
+    #[test]
+    fn can_parse_encoded_auth() {
+        let data = secret_data(serde_json::json!({
+            "auths": {
+                "myregistry.io": { "auth": base64::encode("user:pa:ss") }
+            }
+        }));
+        assert_basic(
+            parse_auth_from_secret_data(&data, "myregistry.io"),
+            "user",
+            "pa:ss",
+        );
+    }
+
+    #[test]
+    fn ignores_malformed_encoded_auth() {
+        let data = secret_data(serde_json::json!({
+            "auths": {
+                "myregistry.io": { "auth": base64::encode("no-separator") }
+            }
+        }));
+        assert!(parse_auth_from_secret_data(&data, "myregistry.io").is_none());
+    }
+
+    #[test]
+    fn can_match_docker_hub_secret_keys() {
+        let data = secret_data(serde_json::json!({
+            "auths": {
+                "https://index.docker.io/v1/": { "username": "user", "password": "pass" }
+            }
+        }));
+        assert_basic(
+            parse_auth_from_secret_data(&data, "docker.io"),
+            "user",
+            "pass",
+        );
+        assert!(parse_auth_from_secret_data(&data, "myregistry.io").is_none());
+    }
+
+    #[test]
+    fn can_match_secret_keys_with_scheme() {
+        let data = secret_data(serde_json::json!({
+            "auths": {
+                "https://myregistry.io:5000": { "username": "user", "password": "pass" }
+            }
+        }));
+        assert_basic(
+            parse_auth_from_secret_data(&data, "myregistry.io:5000"),
+            "user",
+            "pass",
+        );
+    }
+
+    #[test]
+    fn matches_secret_keys_case_insensitively() {
+        let data = secret_data(serde_json::json!({
+            "auths": {
+                "MyRegistry.io": { "username": "user", "password": "pass" }
+            }
+        }));
+        assert_basic(
+            parse_auth_from_secret_data(&data, "myregistry.io"),
+            "user",
+            "pass",
+        );
+    }
+
+    #[test]
+    fn ignores_other_registries() {
+        let data = secret_data(serde_json::json!({
+            "auths": {
+                "myregistry.io": { "username": "user", "password": "pass" }
+            }
+        }));
+        assert!(parse_auth_from_secret_data(&data, "otherregistry.io").is_none());
     }
 }
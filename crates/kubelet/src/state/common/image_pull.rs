@@ -52,7 +52,7 @@ impl<P: GenericProvider> State<P::PodState> for ImagePull<P> {
         let modules = match store.fetch_pod_modules(&pod, &auth_resolver).await {
             Ok(m) => m,
             Err(e) => {
-                error!(error = %e);
+                error!(error = ?e);
                 return Transition::next(self, ImagePullBackoff::<P>::default());
             }
         };
@@ -7,15 +7,18 @@ use oci_distribution::client::ImageData;
 use oci_distribution::secrets::RegistryAuth;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 
+use anyhow::Context;
 use async_trait::async_trait;
 use oci_distribution::Reference;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 
 use crate::container::PullPolicy;
 use crate::pod::Pod;
+use crate::registry::same_registry;
 use crate::store::oci::Client;
 
 /// A store of container modules.
@@ -64,9 +67,9 @@ pub trait Store: Sync {
     ///
     /// This will fetch all of the container modules in parallel.
     ///
-    /// # Panics
-    ///
-    /// This panics if any of the pod's containers do not have an image associated with them
+    /// This fails if any of the pod's containers do not have an image associated
+    /// with them, or if an image reference cannot be parsed. Image references come
+    /// straight from the pod spec and so must not be trusted to be well formed.
     #[instrument(level = "info", skip(self, pod, auth), fields(pod_name = pod.name()))]
     async fn fetch_pod_modules(
         &self,
@@ -76,22 +79,25 @@ pub trait Store: Sync {
         debug!("Fetching all the container modules for pod");
         // Fetch all of the container modules in parallel
         let all_containers = pod.all_containers();
-        let container_module_futures = all_containers.iter().map(move |container| {
+        let container_module_futures = all_containers.iter().map(move |container| async move {
             let reference = container
                 .image()
-                .expect("Could not parse image.")
-                .expect("FATAL ERROR: container must have an image");
-            let pull_policy = container
-                .effective_pull_policy()
-                .expect("Could not identify pull policy.");
-            async move {
-                let registry_authentication = auth.resolve_registry_auth(&reference).await?;
-                Ok((
-                    container.name().to_string(),
-                    self.get(&reference, pull_policy, &registry_authentication)
-                        .await?,
-                ))
-            }
+                .with_context(|| {
+                    format!("Could not parse image for container {}", container.name())
+                })?
+                .ok_or_else(|| anyhow::anyhow!("Container {} has no image", container.name()))?;
+            let pull_policy = container.effective_pull_policy().with_context(|| {
+                format!(
+                    "Could not identify pull policy for container {}",
+                    container.name()
+                )
+            })?;
+            let registry_authentication = auth.resolve_registry_auth(&reference).await?;
+            Ok((
+                container.name().to_string(),
+                self.get(&reference, pull_policy, &registry_authentication)
+                    .await?,
+            ))
         });
 
         // Collect the container modules into a HashMap for quick lookup
@@ -107,9 +113,105 @@ pub trait Store: Sync {
 pub struct LocalStore<S: Storer, C: Client> {
     storer: Arc<RwLock<S>>,
     client: Arc<Mutex<C>>,
+    revalidation_interval: Option<Duration>,
+    last_validated: Arc<RwLock<HashMap<Reference, Instant>>>,
+    allowed_registries: Option<Vec<String>>,
+    denied_registries: Vec<String>,
 }
 
-impl<S: Storer, C: Client> LocalStore<S, C> {
+impl<S: Storer, C: Client + Send> LocalStore<S, C> {
+    /// Revalidate cached images against the registry at most once per `interval`.
+    ///
+    /// By default a [`PullPolicy::Always`] pull asks the registry for the current
+    /// digest every time, and a [`PullPolicy::IfNotPresent`] pull never does once
+    /// the image is cached. With an interval set, both check the digest only if it
+    /// has not been confirmed within the interval, and pull again if it changed.
+    /// This trades freshness of mutable tags for fewer registry requests. Images
+    /// pinned by digest are never revalidated under `IfNotPresent`, as their
+    /// contents cannot change. If an `IfNotPresent` revalidation fails, for
+    /// example because the registry is unreachable, the cached module is used.
+    ///
+    /// Validation times are kept in memory, so the first pull of a cached image
+    /// after a restart always revalidates it.
+    pub fn with_revalidation_interval(mut self, interval: Duration) -> Self {
+        self.revalidation_interval = Some(interval);
+        self
+    }
+
+    /// Only serve images from the given registries. Images from any other
+    /// registry are rejected before the cache or the network is consulted.
+    pub fn with_allowed_registries(mut self, registries: Vec<String>) -> Self {
+        self.allowed_registries = Some(registries);
+        self
+    }
+
+    /// Never serve images from the given registries, even if they are already
+    /// cached. A denied registry is rejected even if it is also allowed.
+    pub fn with_denied_registries(mut self, registries: Vec<String>) -> Self {
+        self.denied_registries = registries;
+        self
+    }
+
+    fn check_registry_permitted(&self, image_ref: &Reference) -> anyhow::Result<()> {
+        let registry = image_ref.registry();
+        let listed = |registries: &[String]| registries.iter().any(|r| same_registry(r, registry));
+        if listed(&self.denied_registries) {
+            return Err(anyhow::anyhow!(
+                "Image ref {} is from denied registry {}",
+                image_ref,
+                registry
+            ));
+        }
+        match &self.allowed_registries {
+            Some(allowed) if !listed(allowed) => Err(anyhow::anyhow!(
+                "Image ref {} is from registry {} which is not in the allowed registries",
+                image_ref,
+                registry
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    async fn recently_validated(&self, image_ref: &Reference) -> bool {
+        let interval = match self.revalidation_interval {
+            Some(interval) => interval,
+            None => return false,
+        };
+        let last_validated = self.last_validated.read().await;
+        matches!(last_validated.get(image_ref), Some(validated) if validated.elapsed() < interval)
+            && self.storer.read().await.is_present(image_ref).await
+    }
+
+    async fn mark_validated(&self, image_ref: &Reference) {
+        if self.revalidation_interval.is_some() {
+            self.last_validated
+                .write()
+                .await
+                .insert(image_ref.clone(), Instant::now());
+        }
+    }
+
+    async fn revalidate(&self, image_ref: &Reference, auth: &RegistryAuth) -> anyhow::Result<()> {
+        let digest = self
+            .client
+            .lock()
+            .await
+            .fetch_digest(image_ref, auth)
+            .await?;
+        let already_got_with_digest = self
+            .storer
+            .read()
+            .await
+            .is_present_with_digest(image_ref, digest)
+            .await;
+        if already_got_with_digest {
+            self.mark_validated(image_ref).await;
+            Ok(())
+        } else {
+            self.pull(image_ref, auth).await
+        }
+    }
+
     #[instrument(level = "info", skip(self, auth))]
     async fn pull(&self, image_ref: &Reference, auth: &RegistryAuth) -> anyhow::Result<()> {
         debug!("Pulling image ref from registry");
@@ -119,6 +221,7 @@ impl<S: Storer, C: Client> LocalStore<S, C> {
             .await
             .store(image_ref, image_data)
             .await?;
+        self.mark_validated(image_ref).await;
         Ok(())
     }
 }
@@ -131,27 +234,25 @@ impl<S: Storer + Sync + Send, C: Client + Sync + Send> Store for LocalStore<S, C
         pull_policy: PullPolicy,
         auth: &RegistryAuth,
     ) -> anyhow::Result<Vec<u8>> {
+        self.check_registry_permitted(image_ref)?;
         match pull_policy {
             PullPolicy::IfNotPresent => {
                 if !self.storer.read().await.is_present(image_ref).await {
                     self.pull(image_ref, auth).await?
+                } else if self.revalidation_interval.is_some()
+                    && image_ref.digest().is_none()
+                    && !self.recently_validated(image_ref).await
+                {
+                    // The image is already cached, so a registry that cannot
+                    // be reached must not stop the pod from running
+                    if let Err(e) = self.revalidate(image_ref, auth).await {
+                        warn!(error = %e, %image_ref, "Could not revalidate cached image, using cached module");
+                    }
                 }
             }
             PullPolicy::Always => {
-                let digest = self
-                    .client
-                    .lock()
-                    .await
-                    .fetch_digest(image_ref, auth)
-                    .await?;
-                let already_got_with_digest = self
-                    .storer
-                    .read()
-                    .await
-                    .is_present_with_digest(image_ref, digest)
-                    .await;
-                if !already_got_with_digest {
-                    self.pull(image_ref, auth).await?
+                if !self.recently_validated(image_ref).await {
+                    self.revalidate(image_ref, auth).await?
                 }
             }
             PullPolicy::Never => (),
@@ -181,3 +282,79 @@ pub trait Storer {
     /// Whether the specified module is already present in the backing store with the specified digest.
     async fn is_present_with_digest(&self, image_ref: &Reference, digest: String) -> bool;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::secret::RegistryAuthResolver;
+    use http::{Request as HttpRequest, Response as HttpResponse};
+    use hyper::Body;
+    use k8s_openapi::api::core::v1::Pod as KubePod;
+    use tower_test::mock;
+
+    struct UnreachableStore;
+
+    #[async_trait]
+    impl Store for UnreachableStore {
+        async fn get(
+            &self,
+            _image_ref: &Reference,
+            _pull_policy: PullPolicy,
+            _auth: &RegistryAuth,
+        ) -> anyhow::Result<Vec<u8>> {
+            Err(anyhow::anyhow!("store should not be reached"))
+        }
+    }
+
+    fn pod_with_container(container: serde_json::Value) -> Pod {
+        let pod: KubePod = serde_json::from_value(serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": "test-pod",
+                "namespace": "default",
+            },
+            "spec": {
+                "containers": [container],
+            },
+        }))
+        .unwrap();
+        Pod::from(pod)
+    }
+
+    fn registry_auth_resolver(pod: &Pod) -> RegistryAuthResolver {
+        // Both failures are detected before any secrets are resolved, so the
+        // mock service never has to answer a request
+        let (mock_service, _handle) = mock::pair::<HttpRequest<Body>, HttpResponse<Body>>();
+        RegistryAuthResolver::new(kube::Client::new(mock_service, "default"), pod)
+    }
+
+    #[tokio::test]
+    async fn fetch_pod_modules_fails_on_unparseable_image() {
+        let pod = pod_with_container(serde_json::json!({
+            "name": "bad-image",
+            "image": "Not/A Valid:Image",
+        }));
+        let auth = registry_auth_resolver(&pod);
+        let err = UnreachableStore
+            .fetch_pod_modules(&pod, &auth)
+            .await
+            .expect_err("expected an unparseable image to fail");
+        assert!(err.to_string().contains("bad-image"), "{:#}", err);
+    }
+
+    #[tokio::test]
+    async fn fetch_pod_modules_fails_on_invalid_pull_policy() {
+        let pod = pod_with_container(serde_json::json!({
+            "name": "bad-policy",
+            "image": "foo/bar:1.0",
+            "imagePullPolicy": "IfMoonMadeOfGreenCheese",
+        }));
+        let auth = registry_auth_resolver(&pod);
+        let err = UnreachableStore
+            .fetch_pod_modules(&pod, &auth)
+            .await
+            .expect_err("expected an invalid pull policy to fail");
+        assert!(err.to_string().contains("bad-policy"), "{:#}", err);
+    }
+}
@@ -27,6 +27,10 @@ impl<C: Client + Send> FileStore<C> {
                 root_dir: root_dir.as_ref().into(),
             })),
             client: Arc::new(Mutex::new(client)),
+            revalidation_interval: None,
+            last_validated: Default::default(),
+            allowed_registries: None,
+            denied_registries: vec![],
         }
     }
 }
@@ -44,9 +48,15 @@ impl FileStorer {
     }
 
     fn pull_path(&self, r: &Reference) -> PathBuf {
-        let mut path = self.root_dir.join(r.registry());
+        // Registry host names are case-insensitive but repository paths are not
+        let mut path = self.root_dir.join(r.registry().to_ascii_lowercase());
         path.push(r.repository());
-        path.push(r.tag().unwrap_or("latest"));
+        match r.digest() {
+            // The `@` keeps digest directories from colliding with tags, and
+            // the `:` is replaced because it is not valid in Windows file names
+            Some(digest) => path.push(format!("@{}", digest.replace(':', "_"))),
+            None => path.push(r.tag().unwrap_or("latest")),
+        }
         path
     }
 
@@ -112,6 +122,10 @@ impl<C: Client + Send> Clone for FileStore<C> {
         Self {
             storer: self.storer.clone(),
             client: self.client.clone(),
+            revalidation_interval: self.revalidation_interval,
+            last_validated: self.last_validated.clone(),
+            allowed_registries: self.allowed_registries.clone(),
+            denied_registries: self.denied_registries.clone(),
         }
     }
 }
@@ -158,6 +172,30 @@ mod test {
         );
     }
 
+    #[test]
+    fn digest_references_default_to_if_not_present() -> anyhow::Result<()> {
+        let digest_ref = Reference::try_from(
+            "foo/bar:latest@sha256:6b86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b",
+        )?;
+        assert_eq!(
+            PullPolicy::IfNotPresent,
+            PullPolicy::parse_effective(None, Some(digest_ref))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn file_storer_ignores_registry_case() -> anyhow::Result<()> {
+        let storer = FileStorer::new("/modules");
+        let mixed_case = Reference::try_from("MyRegistry.io/app:v1")?;
+        let lower_case = Reference::try_from("myregistry.io/app:v1")?;
+        assert_eq!(storer.pull_path(&lower_case), storer.pull_path(&mixed_case));
+        assert!(storer
+            .pull_path(&mixed_case)
+            .ends_with("myregistry.io/app/v1"));
+        Ok(())
+    }
+
     #[derive(Clone)]
     struct FakeImageClient {
         images: Arc<RwLock<HashMap<String, ImageData>>>,
@@ -197,6 +235,14 @@ mod test {
                 },
             );
         }
+
+        fn remove(&mut self, key: &str) {
+            let mut images = self
+                .images
+                .write()
+                .expect("should be able to write to images");
+            images.remove(key);
+        }
     }
     #[async_trait]
     impl Client for FakeImageClient {
@@ -354,6 +400,197 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn file_module_store_skips_revalidation_within_interval() -> anyhow::Result<()> {
+        let mut fake_client =
+            FakeImageClient::new(vec![("foo/bar:latest", vec![1, 2, 3], "sha256:123")]);
+        let fake_ref = Reference::try_from("foo/bar:latest")?;
+        let scratch_dir = create_temp_dir();
+        let store = FileStore::new(fake_client.clone(), &scratch_dir.path)
+            .with_revalidation_interval(std::time::Duration::from_secs(3600));
+        let module_bytes_orig = store
+            .get(&fake_ref, PullPolicy::Always, &RegistryAuth::Anonymous)
+            .await?;
+        assert_eq!(3, module_bytes_orig.len());
+        fake_client.update("foo/bar:latest", vec![4, 5, 6, 7], "sha256:4567");
+        let module_bytes_after = store
+            .get(&fake_ref, PullPolicy::Always, &RegistryAuth::Anonymous)
+            .await?;
+        assert_eq!(3, module_bytes_after.len());
+        assert_eq!(2, module_bytes_after[1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn file_module_store_revalidates_after_interval() -> anyhow::Result<()> {
+        let mut fake_client =
+            FakeImageClient::new(vec![("foo/bar:latest", vec![1, 2, 3], "sha256:123")]);
+        let fake_ref = Reference::try_from("foo/bar:latest")?;
+        let scratch_dir = create_temp_dir();
+        let store = FileStore::new(fake_client.clone(), &scratch_dir.path)
+            .with_revalidation_interval(std::time::Duration::from_millis(1));
+        let module_bytes_orig = store
+            .get(&fake_ref, PullPolicy::Always, &RegistryAuth::Anonymous)
+            .await?;
+        assert_eq!(3, module_bytes_orig.len());
+        fake_client.update("foo/bar:latest", vec![4, 5, 6, 7], "sha256:4567");
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let module_bytes_after = store
+            .get(&fake_ref, PullPolicy::Always, &RegistryAuth::Anonymous)
+            .await?;
+        assert_eq!(4, module_bytes_after.len());
+        assert_eq!(5, module_bytes_after[1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn file_module_store_skips_revalidation_within_interval_if_policy_if_not_present(
+    ) -> anyhow::Result<()> {
+        let mut fake_client =
+            FakeImageClient::new(vec![("foo/bar:1.0", vec![1, 2, 3], "sha256:123")]);
+        let fake_ref = Reference::try_from("foo/bar:1.0")?;
+        let scratch_dir = create_temp_dir();
+        let store = FileStore::new(fake_client.clone(), &scratch_dir.path)
+            .with_revalidation_interval(std::time::Duration::from_secs(3600));
+        let module_bytes_orig = store
+            .get(
+                &fake_ref,
+                PullPolicy::IfNotPresent,
+                &RegistryAuth::Anonymous,
+            )
+            .await?;
+        assert_eq!(3, module_bytes_orig.len());
+        fake_client.update("foo/bar:1.0", vec![4, 5, 6, 7], "sha256:4567");
+        let module_bytes_after = store
+            .get(
+                &fake_ref,
+                PullPolicy::IfNotPresent,
+                &RegistryAuth::Anonymous,
+            )
+            .await?;
+        assert_eq!(3, module_bytes_after.len());
+        assert_eq!(2, module_bytes_after[1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn file_module_store_revalidates_after_interval_if_policy_if_not_present(
+    ) -> anyhow::Result<()> {
+        let mut fake_client =
+            FakeImageClient::new(vec![("foo/bar:1.0", vec![1, 2, 3], "sha256:123")]);
+        let fake_ref = Reference::try_from("foo/bar:1.0")?;
+        let scratch_dir = create_temp_dir();
+        let store = FileStore::new(fake_client.clone(), &scratch_dir.path)
+            .with_revalidation_interval(std::time::Duration::from_millis(1));
+        let module_bytes_orig = store
+            .get(
+                &fake_ref,
+                PullPolicy::IfNotPresent,
+                &RegistryAuth::Anonymous,
+            )
+            .await?;
+        assert_eq!(3, module_bytes_orig.len());
+        fake_client.update("foo/bar:1.0", vec![4, 5, 6, 7], "sha256:4567");
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let module_bytes_after = store
+            .get(
+                &fake_ref,
+                PullPolicy::IfNotPresent,
+                &RegistryAuth::Anonymous,
+            )
+            .await?;
+        assert_eq!(4, module_bytes_after.len());
+        assert_eq!(5, module_bytes_after[1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn file_module_store_uses_cached_if_revalidation_fails_if_policy_if_not_present(
+    ) -> anyhow::Result<()> {
+        let mut fake_client =
+            FakeImageClient::new(vec![("foo/bar:1.0", vec![1, 2, 3], "sha256:123")]);
+        let fake_ref = Reference::try_from("foo/bar:1.0")?;
+        let scratch_dir = create_temp_dir();
+        let store = FileStore::new(fake_client.clone(), &scratch_dir.path)
+            .with_revalidation_interval(std::time::Duration::from_millis(1));
+        let module_bytes_orig = store
+            .get(
+                &fake_ref,
+                PullPolicy::IfNotPresent,
+                &RegistryAuth::Anonymous,
+            )
+            .await?;
+        assert_eq!(3, module_bytes_orig.len());
+        fake_client.remove("foo/bar:1.0");
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let module_bytes_after = store
+            .get(
+                &fake_ref,
+                PullPolicy::IfNotPresent,
+                &RegistryAuth::Anonymous,
+            )
+            .await?;
+        assert_eq!(3, module_bytes_after.len());
+        assert_eq!(2, module_bytes_after[1]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn file_module_store_pulls_from_allowed_registry() -> anyhow::Result<()> {
+        let fake_client =
+            FakeImageClient::new(vec![("myregistry.io/bar:1.0", vec![1, 2, 3], "sha256:123")]);
+        let fake_ref = Reference::try_from("myregistry.io/bar:1.0")?;
+        let scratch_dir = create_temp_dir();
+        let store = FileStore::new(fake_client, &scratch_dir.path)
+            .with_allowed_registries(vec!["MyRegistry.io".to_owned()]);
+        let module_bytes = store
+            .get(&fake_ref, PullPolicy::Always, &RegistryAuth::Anonymous)
+            .await?;
+        assert_eq!(3, module_bytes.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn file_module_store_rejects_registry_not_allowed() -> anyhow::Result<()> {
+        let fake_client =
+            FakeImageClient::new(vec![("myregistry.io/bar:1.0", vec![1, 2, 3], "sha256:123")]);
+        let fake_ref = Reference::try_from("myregistry.io/bar:1.0")?;
+        let scratch_dir = create_temp_dir();
+        let store = FileStore::new(fake_client, &scratch_dir.path)
+            .with_allowed_registries(vec!["otherregistry.io".to_owned()]);
+        let module_bytes = store
+            .get(&fake_ref, PullPolicy::Always, &RegistryAuth::Anonymous)
+            .await;
+        assert!(
+            module_bytes.is_err(),
+            "expected get from a registry not in the allowed list to fail but it worked"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn file_module_store_rejects_denied_registry_even_if_cached() -> anyhow::Result<()> {
+        let fake_client =
+            FakeImageClient::new(vec![("myregistry.io/bar:1.0", vec![1, 2, 3], "sha256:123")]);
+        let fake_ref = Reference::try_from("myregistry.io/bar:1.0")?;
+        let scratch_dir = create_temp_dir();
+        let prime_cache = FileStore::new(fake_client.clone(), &scratch_dir.path)
+            .get(&fake_ref, PullPolicy::Always, &RegistryAuth::Anonymous)
+            .await;
+        assert!(prime_cache.is_ok());
+        let store = FileStore::new(fake_client, &scratch_dir.path)
+            .with_allowed_registries(vec!["myregistry.io".to_owned()])
+            .with_denied_registries(vec!["myregistry.io".to_owned()]);
+        let module_bytes = store
+            .get(&fake_ref, PullPolicy::Never, &RegistryAuth::Anonymous)
+            .await;
+        assert!(
+            module_bytes.is_err(),
+            "expected get from a denied registry to fail but it worked"
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn file_module_store_copes_with_no_tag() -> anyhow::Result<()> {
         let fake_client = FakeImageClient::new(vec![("foo/bar", vec![2, 3], "sha256:23")]);
@@ -368,6 +605,32 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn file_module_store_keeps_digest_references_separate_from_tags() -> anyhow::Result<()> {
+        let digest = "sha256:6b86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b";
+        let digest_ref_name = format!("foo/bar@{}", digest);
+        let mut fake_client = FakeImageClient::new(vec![("foo/bar", vec![3, 4], "sha256:34")]);
+        fake_client.update(&digest_ref_name, vec![1, 2, 3], digest);
+        let tag_ref = Reference::try_from("foo/bar")?;
+        let digest_ref = Reference::try_from(digest_ref_name.as_str())?;
+        let scratch_dir = create_temp_dir();
+        let store = FileStore::new(fake_client, &scratch_dir.path);
+        let tag_bytes = store
+            .get(&tag_ref, PullPolicy::Always, &RegistryAuth::Anonymous)
+            .await?;
+        assert_eq!(2, tag_bytes.len());
+        let digest_bytes = store
+            .get(
+                &digest_ref,
+                PullPolicy::IfNotPresent,
+                &RegistryAuth::Anonymous,
+            )
+            .await?;
+        assert_eq!(3, digest_bytes.len());
+        assert_eq!(2, digest_bytes[1]);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn file_module_store_can_pull_if_tag_given_but_policy_omitted() -> anyhow::Result<()> {
         let mut fake_client =
@@ -46,7 +46,17 @@ fn make_store(config: &Config) -> Arc<dyn kubelet::store::Store + Send + Sync> {
     let client = oci_distribution::Client::from_source(config);
     let mut store_path = config.data_dir.join(".oci");
     store_path.push("modules");
-    let file_store = Arc::new(FileStore::new(client, &store_path));
+    let mut file_store = FileStore::new(client, &store_path);
+    if let Some(interval) = config.image_revalidation_interval {
+        file_store = file_store.with_revalidation_interval(interval);
+    }
+    if let Some(registries) = &config.allowed_registries {
+        file_store = file_store.with_allowed_registries(registries.clone());
+    }
+    if let Some(registries) = &config.denied_registries {
+        file_store = file_store.with_denied_registries(registries.clone());
+    }
+    let file_store = Arc::new(file_store);
 
     if config.allow_local_modules {
         file_store.with_override(Arc::new(kubelet::store::fs::FileSystemStore {}))